@@ -0,0 +1,569 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use dns_lookup::lookup_host;
+use regex::Regex;
+use serde::Serialize;
+
+mod native;
+mod signal;
+pub use native::native_ping;
+pub use signal::install_interrupt_handler;
+
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+
+const PING_CMD: &str = "ping";
+const LOG_DEBUG: bool = false;
+
+
+// User-controllable settings for a ping run, translated into the correct per-OS `ping`
+// executable arguments by `ping_stream`. Defaults match the previous hardcoded behaviour of
+// 3 packets, 0.2s apart, with no explicit per-reply timeout.
+#[derive(Clone)]
+pub struct PingConfig {
+    pub count: u32,
+    pub interval_secs: f64,
+    pub timeout_secs: Option<f64>,
+    pub continuous: bool,
+    pub native: bool,
+}
+
+impl Default for PingConfig {
+    fn default() -> Self {
+        PingConfig {
+            count: 3,
+            interval_secs: 0.2,
+            timeout_secs: None,
+            continuous: false,
+            native: false,
+        }
+    }
+}
+
+
+// Pings `host` using whichever backend `config` selects: the native ICMP socket backend when
+// `config.native` is set, falling back to the `Command`-based OS `ping` executable when raw
+// sockets aren't available on this platform (e.g. sandboxing denies the socket), or the
+// `Command`-based backend directly otherwise.
+//
+pub fn ping_with_backend(host: &str, config: &PingConfig) -> PingResult {
+    if config.native {
+        match native_ping(host, config) {
+            Ok(result) => result,
+            Err(_) => ping(host, config),
+        }
+    } else {
+        ping(host, config)
+    }
+}
+
+
+// Pings every host in `hosts` concurrently, one thread per host, bounding how many run at once
+// to `parallel` so pinging hundreds of hosts doesn't exhaust file descriptors. Results are
+// returned in the same order as `hosts`, regardless of which host's thread finishes first.
+//
+pub fn ping_hosts(hosts: &[String], config: &PingConfig, parallel: usize) -> Vec<(String, PingResult)> {
+    let parallel = parallel.max(1);
+    let mut results = Vec::with_capacity(hosts.len());
+
+    for batch in hosts.chunks(parallel) {
+        let handles: Vec<_> = batch.iter().map(|host| {
+            let host = host.clone();
+            let config = config.clone();
+            thread::spawn(move || {
+                let result = ping_with_backend(&host, &config);
+                (host, result)
+            })
+        }).collect();
+
+        for handle in handles {
+            results.push(handle.join().expect("ping worker thread panicked"));
+        }
+    }
+
+    results
+}
+
+
+// Capture type of result from issuing a ping
+pub enum PingResult {
+    ConnectionSuccess(PingStats),
+    ConnectionFailure(String),
+    DNSIssue(String),
+    OSCmndIssue(String),
+}
+
+impl PingResult {
+    // The process exit code a monitoring probe should return for this result, matching the
+    // convention external system-ping wrappers use (0 success, 2 connection failure, 3 DNS
+    // failure, 4 OS command issue) so scripts can branch on `$?` without parsing text.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PingResult::ConnectionSuccess(_) => 0,
+            PingResult::ConnectionFailure(_) => 2,
+            PingResult::DNSIssue(_) => 3,
+            PingResult::OSCmndIssue(_) => 4,
+        }
+    }
+}
+
+
+// A machine-readable, stable summary of a `PingResult`, for a `--format=json` style probe
+// output - e.g. `{"host":..., "status":"success", "avg_ms":..., "loss_pct":..., "message":...}`.
+#[derive(Serialize)]
+pub struct PingReport {
+    pub host: String,
+    pub status: &'static str,
+    pub avg_ms: Option<f64>,
+    pub loss_pct: Option<f64>,
+    pub message: Option<String>,
+}
+
+impl PingReport {
+    pub fn new(host: &str, result: &PingResult) -> Self {
+        let (status, avg_ms, loss_pct, message) = match result {
+            PingResult::ConnectionSuccess(stats) => {
+                ("success", Some(stats.avg_ms), Some(stats.packet_loss_pct), None)
+            }
+            PingResult::ConnectionFailure(message) => {
+                ("connection_failure", None, None, Some(message.clone()))
+            }
+            PingResult::DNSIssue(message) => ("dns_failure", None, None, Some(message.clone())),
+            PingResult::OSCmndIssue(message) => ("os_error", None, None, Some(message.clone())),
+        };
+
+        PingReport { host: host.to_string(), status, avg_ms, loss_pct, message }
+    }
+}
+
+
+// Round-trip latency and packet-loss statistics computed from the per-packet replies and
+// timeouts collected during a ping run (see `stats_from_replies`); the OS ping summary line is
+// not parsed. Latency fields are left as zero when no reply was received.
+#[derive(Default)]
+pub struct PingStats {
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub mdev_ms: f64,
+    pub packets_transmitted: u32,
+    pub packets_received: u32,
+    pub packet_loss_pct: f64,
+}
+
+
+// A single line of live ping output, classified as a successful reply, a timeout, or anything
+// else the OS ping executable printed that doesn't match either pattern.
+pub enum PingMessage {
+    Pong(Duration, String),
+    Timeout(String),
+    Unknown(String),
+}
+
+
+// A live stream of `PingMessage`s read from a spawned `ping` child process, one per line of OS
+// output. Iterate it to process replies as they arrive instead of waiting for the whole run to
+// finish; the iterator ends once the child process exits, or once a Ctrl-C is observed, in
+// which case the child is killed so it's never left orphaned.
+pub struct PingStream {
+    child: Child,
+    receiver: Receiver<PingMessage>,
+}
+
+impl Iterator for PingStream {
+    type Item = PingMessage;
+
+    fn next(&mut self) -> Option<PingMessage> {
+        loop {
+            if signal::interrupted() {
+                let _ = self.child.kill();
+                return None;
+            }
+
+            match self.receiver.recv_timeout(SIGNAL_POLL_INTERVAL) {
+                Ok(message) => return Some(message),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+
+// Spawns the underlying OS ping executable, against the resolved IP address of a host, to
+// perform a network ICMP ping, returning a live stream of per-line `PingMessage`s. The hostname
+// is resolved via the system resolver up front, before any ping is spawned, so DNS failures are
+// classified accurately regardless of OS locale; OS-command issues are likewise classified
+// before any streaming begins. Both are returned as an `Err(PingResult)`; resolving and spawning
+// successfully hands back an iterator the caller can drain at its own pace.
+//
+pub fn ping_stream(host: &str, config: &PingConfig) -> Result<PingStream, PingResult> {
+    if config.continuous {
+        signal::install_interrupt_handler();
+    }
+
+    // A Ctrl-C already observed (e.g. from an earlier host in a multi-host --continuous run)
+    // means the user wants to stop - never spawn another ping for it, and never report it as a
+    // success just because it was never actually attempted.
+    if signal::interrupted() {
+        return Err(PingResult::ConnectionFailure(format!("Ping for host '{}' skipped - \
+            interrupted by Ctrl-C before it could start", host)));
+    }
+
+    let resolved_ip = match lookup_host(host) {
+        Ok(ips) => match ips.into_iter().next() {
+            Some(ip) => ip,
+            None => return Err(PingResult::DNSIssue(format!("DNS lookup for '{}' returned no \
+                addresses", host))),
+        },
+        Err(e) => return Err(PingResult::DNSIssue(format!("Unable to resolve hostname '{}' - \
+            error: {}", host, e))),
+    };
+
+    let mut cmd = Command::new(PING_CMD);
+
+    for arg in build_os_args(config) {
+        cmd.arg(arg);
+    }
+
+    let mut child = match cmd.arg(resolved_ip.to_string()).stdout(Stdio::piped())
+        .stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            debug_process_error(&e);
+            return Err(os_cmnd_issue(&e));
+        }
+    };
+
+    let stdout = child.stdout.take().expect("child spawned with piped stdout");
+    let stderr = child.stderr.take().expect("child spawned with piped stderr");
+    let reader = BufReader::new(stdout);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in reader.lines().map_while(Result::ok) {
+            if sender.send(classify_line(&line)).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Drain stderr on its own thread purely to keep the pipe from filling up and blocking the
+    // child's writes - osping doesn't classify anything from stderr, so the content is discarded.
+    thread::spawn(move || {
+        for _line in BufReader::new(stderr).lines().map_while(Result::ok) {}
+    });
+
+    Ok(PingStream { child, receiver })
+}
+
+
+// Uses the underlying OS ping executable, on the host, to perform a network ICMP ping against a
+// host (DNS name or IP address), returning a single result typed to indicate success (with
+// round-trip latency and loss statistics) or the type of failure. Built on top of `ping_stream`,
+// draining it to completion rather than blocking on one combined process output.
+//
+pub fn ping(host: &str, config: &PingConfig) -> PingResult {
+    let mut stream = match ping_stream(host, config) {
+        Ok(stream) => stream,
+        Err(result) => return result,
+    };
+
+    let mut rtts = Vec::new();
+    let mut timeouts = 0u32;
+
+    for message in &mut stream {
+        match message {
+            PingMessage::Pong(rtt, _) => rtts.push(rtt),
+            PingMessage::Timeout(_) => timeouts += 1,
+            PingMessage::Unknown(_) => {}
+        }
+    }
+
+    let status = stream.child.wait();
+    debug_process_status(&status);
+
+    if rtts.is_empty() {
+        // No reply lines were recognized at all - either explicit timeouts were seen, or nothing
+        // matched any known pattern. Either way, only trust the child's own exit status to call
+        // it a success; default to failure rather than silently reporting a dead host as
+        // reachable.
+        let exited_successfully = matches!(status, Ok(s) if s.success());
+
+        if timeouts > 0 || !exited_successfully {
+            PingResult::ConnectionFailure(format!("Host '{}' cannot be reached over a network \
+                ICMP Ping", host))
+        } else {
+            PingResult::ConnectionSuccess(stats_from_replies(&rtts, timeouts))
+        }
+    } else {
+        PingResult::ConnectionSuccess(stats_from_replies(&rtts, timeouts))
+    }
+}
+
+
+// Builds latency/loss statistics out of the round-trip times and timeout count collected while
+// draining a `PingStream`, mirroring the fields the OS ping summary line would have reported.
+//
+pub(crate) fn stats_from_replies(rtts: &[Duration], timeouts: u32) -> PingStats {
+    let packets_transmitted = rtts.len() as u32 + timeouts;
+    let packet_loss_pct = if packets_transmitted > 0 {
+        (timeouts as f64 / packets_transmitted as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let (min_ms, avg_ms, max_ms, mdev_ms) = if rtts.is_empty() {
+        (0.0, 0.0, 0.0, 0.0)
+    } else {
+        let millis: Vec<f64> = rtts.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        let sum: f64 = millis.iter().sum();
+        let avg_ms = sum / millis.len() as f64;
+        let min_ms = millis.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max_ms = millis.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let variance = millis.iter().map(|m| (m - avg_ms).powi(2)).sum::<f64>()
+            / millis.len() as f64;
+        (min_ms, avg_ms, max_ms, variance.sqrt())
+    };
+
+    PingStats { min_ms, avg_ms, max_ms, mdev_ms, packets_transmitted,
+        packets_received: rtts.len() as u32, packet_loss_pct }
+}
+
+
+// Translates a `PingConfig` into the correct sequence of arguments for the local OS's `ping`
+// executable - Unix and Windows disagree on flag letters and on what a timeout is measured in
+// (seconds vs milliseconds).
+//
+fn build_os_args(config: &PingConfig) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if cfg!(windows) {
+        if config.continuous {
+            args.push("-t".to_string());
+        } else {
+            args.push("-n".to_string());
+            args.push(config.count.to_string());
+        }
+
+        if let Some(timeout_secs) = config.timeout_secs {
+            args.push("-w".to_string());
+            args.push(((timeout_secs * 1000.0) as u64).to_string());
+        }
+    } else {
+        if !config.continuous {
+            args.push("-c".to_string());
+            args.push(config.count.to_string());
+        }
+
+        args.push("-i".to_string());
+        args.push(config.interval_secs.to_string());
+
+        if let Some(timeout_secs) = config.timeout_secs {
+            args.push("-W".to_string());
+            args.push(timeout_secs.to_string());
+        }
+    }
+
+    args
+}
+
+
+// Substrings, matched case-insensitively, that real OS ping executables print for a packet that
+// didn't get a reply - either an explicit timeout or the destination/gateway reporting the host
+// unreachable - plus the summary line of a run in which no packet got a reply.
+const TIMEOUT_MARKERS: [&str; 4] =
+    ["request timeout", "request timed out", "unreachable", "100% packet loss"];
+
+// Classifies a single line of OS ping output as a successful reply (with its round-trip time),
+// a timeout, or anything else that doesn't match either pattern.
+//
+fn classify_line(line: &str) -> PingMessage {
+    static REPLY_RE: OnceLock<Regex> = OnceLock::new();
+    let reply_re = REPLY_RE.get_or_init(|| Regex::new(r"time[=<]([\d.]+)\s*ms").unwrap());
+
+    if let Some(caps) = reply_re.captures(line) {
+        if let Ok(millis) = caps[1].parse::<f64>() {
+            return PingMessage::Pong(Duration::from_secs_f64(millis / 1000.0), line.to_string());
+        }
+    }
+
+    let lowercase_line = line.to_lowercase();
+
+    if TIMEOUT_MARKERS.iter().any(|marker| lowercase_line.contains(marker)) {
+        return PingMessage::Timeout(line.to_string());
+    }
+
+    PingMessage::Unknown(line.to_string())
+}
+
+
+// Maps an OS error from attempting to spawn the `ping` executable into a `PingResult`.
+//
+fn os_cmnd_issue(e: &std::io::Error) -> PingResult {
+    if e.kind() == ErrorKind::NotFound {
+        PingResult::OSCmndIssue("Unable to locate 'ping' executable in the local OS \
+            environment - ensure this executable is on your environment path (check your \
+            PATH environment variable)".to_string())
+    } else if e.kind() == ErrorKind::PermissionDenied {
+        PingResult::OSCmndIssue("Unable to run the 'ping' executable in the local OS \
+            environment due to lack of permissions - ensure the 'ping' command on your OS \
+            is assigned with executable permissions for your OS user running this \
+            tool".to_string())
+    } else {
+        PingResult::OSCmndIssue(format!("Unable to invoke the 'ping' executable on the \
+            underlying OS. OS output received: '{}'", e))
+    }
+}
+
+
+// Print out the exit status of the ping child process if the debug 'constant' is set to true
+//
+fn debug_process_status(status: &std::io::Result<std::process::ExitStatus>) {
+    if LOG_DEBUG {
+        println!("\n ---------------------");
+        println!(" Process result:");
+        println!("  * Status: {:?}", status);
+        println!(" ---------------------\n");
+    }
+}
+
+
+// Print out the ping command error if the debug 'constant' is set to true
+//
+fn debug_process_error(error: &dyn Error) {
+    if LOG_DEBUG {
+        println!("\n ---------------------");
+        println!(" Process error:");
+        println!("  * Message: {:?}", error);
+        println!(" ---------------------\n");
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn assert_ping_good_host() {
+        let config = PingConfig::default();
+        assert!(matches!(ping("www.google.com", &config), PingResult::ConnectionSuccess(_)))
+    }
+
+
+    #[test]
+    fn assert_noping_bad_host() {
+        let config = PingConfig::default();
+        assert!(matches!(
+            ping("www.doesnotexistindnshost.com", &config),
+            PingResult::DNSIssue(_)
+        ))
+    }
+
+
+    #[test]
+    fn classify_line_recognizes_unix_reply() {
+        assert!(matches!(
+            classify_line("64 bytes from 1.1.1.1: icmp_seq=1 ttl=56 time=12.3 ms"),
+            PingMessage::Pong(_, _)
+        ));
+    }
+
+    #[test]
+    fn classify_line_recognizes_windows_sub_millisecond_reply() {
+        assert!(matches!(
+            classify_line("Reply from 1.1.1.1: bytes=32 time<1ms TTL=56"),
+            PingMessage::Pong(_, _)
+        ));
+    }
+
+    #[test]
+    fn classify_line_recognizes_lowercase_timeout() {
+        assert!(matches!(
+            classify_line("Request timeout for icmp_seq 2"),
+            PingMessage::Timeout(_)
+        ));
+    }
+
+    #[test]
+    fn classify_line_recognizes_capitalized_destination_unreachable() {
+        assert!(matches!(
+            classify_line("From 1.1.1.1 icmp_seq=1 Destination Host Unreachable"),
+            PingMessage::Timeout(_)
+        ));
+    }
+
+    #[test]
+    fn classify_line_falls_back_to_unknown() {
+        assert!(matches!(
+            classify_line("PING 1.1.1.1 (1.1.1.1): 56 data bytes"),
+            PingMessage::Unknown(_)
+        ));
+    }
+
+
+    #[test]
+    fn stats_from_replies_computes_loss_and_latency() {
+        let rtts = vec![Duration::from_millis(10), Duration::from_millis(20)];
+        let stats = stats_from_replies(&rtts, 1);
+        assert_eq!(stats.packets_transmitted, 3);
+        assert_eq!(stats.packets_received, 2);
+        assert!((stats.packet_loss_pct - 33.333).abs() < 0.01);
+        assert!((stats.avg_ms - 15.0).abs() < 0.01);
+        assert!((stats.min_ms - 10.0).abs() < 0.01);
+        assert!((stats.max_ms - 20.0).abs() < 0.01);
+    }
+
+
+    #[test]
+    fn stats_from_replies_with_no_packets_is_all_zero() {
+        let stats = stats_from_replies(&[], 0);
+        assert_eq!(stats.packets_transmitted, 0);
+        assert_eq!(stats.packet_loss_pct, 0.0);
+    }
+
+
+    #[test]
+    fn build_os_args_unix_includes_count_and_interval() {
+        if !cfg!(windows) {
+            let config = PingConfig { count: 5, interval_secs: 0.5, ..PingConfig::default() };
+            let args = build_os_args(&config);
+            assert_eq!(args, vec!["-c", "5", "-i", "0.5"]);
+        }
+    }
+
+    #[test]
+    fn build_os_args_unix_continuous_omits_count() {
+        if !cfg!(windows) {
+            let config = PingConfig { continuous: true, ..PingConfig::default() };
+            let args = build_os_args(&config);
+            assert!(!args.contains(&"-c".to_string()));
+        }
+    }
+
+
+    #[test]
+    fn ping_report_success_includes_latency_and_loss() {
+        let stats = PingStats { avg_ms: 12.5, packet_loss_pct: 0.0, ..PingStats::default() };
+        let report = PingReport::new("example.com", &PingResult::ConnectionSuccess(stats));
+        assert_eq!(report.status, "success");
+        assert_eq!(report.avg_ms, Some(12.5));
+        assert_eq!(report.loss_pct, Some(0.0));
+        assert!(report.message.is_none());
+    }
+
+    #[test]
+    fn ping_report_dns_failure_carries_message_and_no_stats() {
+        let report = PingReport::new("bad.host", &PingResult::DNSIssue("boom".to_string()));
+        assert_eq!(report.status, "dns_failure");
+        assert_eq!(report.avg_ms, None);
+        assert_eq!(report.message.as_deref(), Some("boom"));
+    }
+}
@@ -1,168 +1,149 @@
-use std::env;
-use std::error::Error;
-use std::io::ErrorKind;
-use std::process::{exit, Command, Output};
-
-
-const PING_CMD: &str = "ping";
-const UNIX_INTERVAL_ARG: &str = "-i 0.2";
-const UNIX_COUNT_ARG: &str = "-c 3";
-const WINDOWS_COUNT_ARG_KEY: &str = "-n";
-const WINDOWS_COUNT_ARG_VAL: &str = "3";
-const LOG_DEBUG: bool = false;
-
-
-// Capture type of result from issuing a ping
-enum PingResult {
-    ConnectionSuccess,
-    ConnectionFailure(String),
-    DNSIssue(String),
-    OSCmndIssue(String),
+use std::process::exit;
+use clap::{Parser, ValueEnum};
+use osping::{ping_hosts, PingConfig, PingReport, PingResult};
+
+
+// Command-line options controlling how osping drives the underlying OS ping executable.
+#[derive(Parser)]
+#[command(name = "osping")]
+struct Cli {
+    /// Host names or IP addresses to ping
+    #[arg(required = true)]
+    hosts: Vec<String>,
+
+    /// Number of echo requests to send
+    #[arg(long, default_value_t = 3)]
+    count: u32,
+
+    /// Seconds to wait between sending each echo request
+    #[arg(long, default_value_t = 0.2)]
+    interval: f64,
+
+    /// Seconds to wait for each individual reply before considering it lost
+    #[arg(long)]
+    timeout: Option<f64>,
+
+    /// Ping indefinitely until interrupted, ignoring --count
+    #[arg(short = 't', long)]
+    continuous: bool,
+
+    /// Send ICMP echo requests directly over a raw/datagram socket instead of shelling out to
+    /// the OS 'ping' executable, falling back to it if native sockets aren't available
+    #[arg(long)]
+    native: bool,
+
+    /// Output format, for scripting against osping as a monitoring probe
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Maximum number of hosts to ping concurrently when several are given
+    #[arg(long, default_value_t = 16)]
+    parallel: usize,
 }
 
+#[derive(Clone, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
 
-// Main function to call host OS ping executable with a host argument passed to this application
-//
-fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    if args.len() < 2 {
-        eprintln!("\nERROR: A host must be provided as an argument\n");
-        exit(1);
-    }
-
-    let host = &args[1];
-
-    match ping(host) {
-        PingResult::ConnectionSuccess => println!("CONNECTION SUCCESS - Network ICMP Ping \
-            successful for host '{}'", host),
-        PingResult::ConnectionFailure(message) => println!("CONNECTION FAILURE - Network ICMP Ping \
-            unsuccessful for host '{}' - error: {}", host, message),
-        PingResult::DNSIssue(message) => println!("DNS FAILURE - DNS lookup issue for hostname \
-            '{}' - error: {}", host, message),
-        PingResult::OSCmndIssue(message) => println!("OS PING COMMAND ISSUE - problem executing \
-            OS ping utility - error: {}", message),
+impl From<&Cli> for PingConfig {
+    fn from(cli: &Cli) -> Self {
+        PingConfig {
+            count: cli.count,
+            interval_secs: cli.interval,
+            timeout_secs: cli.timeout,
+            continuous: cli.continuous,
+            native: cli.native,
+        }
     }
 }
 
 
-// Uses the underlying OS ping executable, on the host, to perform a network ICMP ping against a
-// host (DNS name or IP address), returning a result typed to indicate success or the type of
-// failure
+// Main function to call host OS ping executable with one or more host arguments passed to this
+// application. A single host behaves exactly as before; several hosts are pinged concurrently
+// (bounded by --parallel) and reported as a per-host table plus a summary. Exits with a distinct
+// code per `PingResult` variant for a single host (0 success, 2 connection failure, 3 DNS
+// failure, 4 OS command issue), matching the convention external system-ping wrappers use for
+// monitoring probes; for several hosts the exit code reflects the worst result seen.
 //
-fn ping(host: &str) -> PingResult {
-    let mut cmd = &mut Command::new(PING_CMD);
-
-    if cfg!(windows) {
-        cmd = cmd.arg(WINDOWS_COUNT_ARG_KEY).arg(WINDOWS_COUNT_ARG_VAL);
-    } else {
-        cmd = cmd.arg(UNIX_COUNT_ARG).arg(UNIX_INTERVAL_ARG);
-    }
-
-    match cmd.arg(host).output() {
-        Ok(output) => {
-            debug_process_output(&output);
-
-            if output.status.success() {
-                PingResult::ConnectionSuccess
-            } else if !cfg!(windows) && (output.status.code().unwrap_or(-1) == 1) {
-                // Unix  (Unix's Ping uses code 1 for connection error & code 2 for other errors)
-                PingResult::ConnectionFailure(format!("Host '{}' cannot be reached over a network \
-                    ICMP Ping", host))
-            } else {
-                // Windows for all errors, Unix for non-connection related errors
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-
-                if stdout.contains("could not find host") {
-                    // Windows
-                    PingResult::DNSIssue(format!("Ping returned error indicating no DNS entry for \
-                        '{}'. OS output received: '{}'", host, stdout))
-                } else if stderr.contains("not known") {
-                    // Unix
-                    PingResult::DNSIssue(format!("Ping returned error indicating no DNS entry for \
-                        '{}'. OS output received: '{}'", host, stderr))
-                } else if stderr.contains("associated with hostname") {
-                    // Unix
-                    PingResult::DNSIssue(format!("Ping returned error indicating the DNS entry is \
-                        not a hostname associated with an IP address. OS output received: '{}'",
-                        stderr))
-                } else if cfg!(windows) {
-                    // Windows (Window's Ping uses stdout for errors rather than stderr
-                    PingResult::ConnectionFailure(format!("Ping returned error. OS output received \
-                        - stdout: '{}' - stderr: '{}'", stdout, stderr))
-                } else {
-                    // Unix
-                    PingResult::ConnectionFailure(format!("Ping returned error. OS output \
-                        received: '{}'", stderr))
-                }
+fn main() {
+    let cli = Cli::parse();
+    let config = PingConfig::from(&cli);
+    let results = ping_hosts(&cli.hosts, &config, cli.parallel);
+    let exit_code = results.iter().map(|(_, result)| result.exit_code()).max().unwrap_or(0);
+
+    match &cli.format {
+        OutputFormat::Json => {
+            for (host, result) in &results {
+                let report = PingReport::new(host, result);
+                println!("{}", serde_json::to_string(&report).expect("PingReport is always valid JSON"));
             }
         }
-        Err(e) => {
-            // Errors related to not being able to invoke Ping executable both on Windows & Unix
-            debug_process_error(&e);
-            if e.kind() == ErrorKind::NotFound {
-                PingResult::OSCmndIssue("Unable to locate 'ping' executable in the local OS \
-                    environment - ensure this executable is on your environment path (check your \
-                    PATH environment variable)".to_string())
-            } else if e.kind() == ErrorKind::PermissionDenied {
-                PingResult::OSCmndIssue("Unable to run the 'ping' executable in the local OS \
-                    environment due to lack of permissions - ensure the 'ping' command on your OS \
-                    is assigned with executable permissions for your OS user running this \
-                    tool".to_string())
-            } else {
-                PingResult::OSCmndIssue(format!("Unable to invoke the 'ping' executable on the \
-                    underlying OS. OS output received: '{}'", e.to_string()))
-            }
+        OutputFormat::Text if results.len() == 1 => {
+            let (host, result) = &results[0];
+            print_text_result(host, result);
+        }
+        OutputFormat::Text => {
+            print_table_and_summary(&results);
         }
     }
+
+    exit(exit_code);
 }
 
 
-// Print out the ping command output if the debug 'constant' is set to true
+// Prints a single host's result in osping's original one-line human-readable format.
 //
-fn debug_process_output(output: &Output) {
-    if LOG_DEBUG {
-        println!("\n ---------------------");
-        println!(" Process result:");
-        println!("  * Status: {}", output.status);
-        println!("  * Stdout: {}", String::from_utf8_lossy(&output.stdout));
-        println!("  * Stderr: {}", String::from_utf8_lossy(&output.stderr));
-        println!(" ---------------------\n");
+fn print_text_result(host: &str, result: &PingResult) {
+    match result {
+        PingResult::ConnectionSuccess(stats) => println!("CONNECTION SUCCESS - Network ICMP \
+            Ping successful for host '{}' - {}/{} packets received, {:.0}% loss, \
+            min/avg/max = {:.1}/{:.1}/{:.1} ms", host, stats.packets_received,
+            stats.packets_transmitted, stats.packet_loss_pct, stats.min_ms, stats.avg_ms,
+            stats.max_ms),
+        PingResult::ConnectionFailure(message) => println!("CONNECTION FAILURE - Network ICMP \
+            Ping unsuccessful for host '{}' - error: {}", host, message),
+        PingResult::DNSIssue(message) => println!("DNS FAILURE - DNS lookup issue for hostname \
+            '{}' - error: {}", host, message),
+        PingResult::OSCmndIssue(message) => println!("OS PING COMMAND ISSUE - problem executing \
+            OS ping utility - error: {}", message),
     }
 }
 
 
-// Print out the ping command error if the debug 'constant' is set to true
+// Prints a per-host table of results followed by a summary: how many hosts were reachable,
+// which had DNS failures, and the aggregate best/worst latency across all reachable hosts.
 //
-fn debug_process_error(error: &dyn Error) {
-    if LOG_DEBUG {
-        println!("\n ---------------------");
-        println!(" Process error:");
-        println!("  * Message: {:?}", error);
-        println!(" ---------------------\n");
+fn print_table_and_summary(results: &[(String, PingResult)]) {
+    for (host, result) in results {
+        print_text_result(host, result);
     }
-}
 
+    let mut reachable = 0;
+    let mut dns_failures = Vec::new();
+    let mut best_ms = f64::INFINITY;
+    let mut worst_ms = f64::NEG_INFINITY;
+
+    for (host, result) in results {
+        match result {
+            PingResult::ConnectionSuccess(stats) => {
+                reachable += 1;
+                best_ms = best_ms.min(stats.avg_ms);
+                worst_ms = worst_ms.max(stats.avg_ms);
+            }
+            PingResult::DNSIssue(_) => dns_failures.push(host.as_str()),
+            PingResult::ConnectionFailure(_) | PingResult::OSCmndIssue(_) => {}
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
+    println!("\nSUMMARY - {}/{} hosts reachable", reachable, results.len());
 
-    #[test]
-    fn assert_ping_good_host() {
-        assert!(if let PingResult::ConnectionSuccess = ping("www.google.com") { true } else { false })
+    if !dns_failures.is_empty() {
+        println!("  * DNS failures: {}", dns_failures.join(", "));
     }
 
-
-    #[test]
-    fn assert_noping_bad_host() {
-        assert!(
-            if let PingResult::DNSIssue(_) = ping("www.doesnotexistindnshost.com") 
-                { true } 
-            else
-                { false }
-        )
+    if reachable > 0 {
+        println!("  * Latency (avg): best {:.1}ms, worst {:.1}ms", best_ms, worst_ms);
     }
 }
@@ -0,0 +1,244 @@
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+use dns_lookup::lookup_host;
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+use crate::{signal, stats_from_replies, PingConfig, PingResult};
+
+
+const ICMP_ECHO_REQUEST_V4: u8 = 8;
+const ICMP_ECHO_REQUEST_V6: u8 = 128;
+const ICMP_ECHO_REPLY_V4: u8 = 0;
+const ICMP_ECHO_REPLY_V6: u8 = 129;
+const DEFAULT_TIMEOUT_SECS: f64 = 2.0;
+
+
+// Sends ICMP echo requests directly over an unprivileged datagram socket (`SOCK_DGRAM` /
+// `IPPROTO_ICMP`), bypassing the OS `ping` executable entirely and timing replies ourselves.
+// Returns `Err` only when the socket itself can't be created, so the caller can fall back to the
+// `Command`-based backend; every other outcome - DNS failures, timeouts, success - comes back as
+// an `Ok(PingResult)` so callers can treat it identically to the OS-backed `ping`.
+//
+pub fn native_ping(host: &str, config: &PingConfig) -> Result<PingResult, io::Error> {
+    // A Ctrl-C already observed (e.g. from an earlier host in a multi-host --continuous run)
+    // means the user wants to stop - never send another echo request, and never report the host
+    // as a success just because it was never actually attempted.
+    if signal::interrupted() {
+        return Ok(PingResult::ConnectionFailure(format!("Ping for host '{}' skipped - \
+            interrupted by Ctrl-C before it could start", host)));
+    }
+
+    let ip = match lookup_host(host) {
+        Ok(ips) => match ips.into_iter().next() {
+            Some(ip) => ip,
+            None => return Ok(PingResult::DNSIssue(format!("DNS lookup for '{}' returned no \
+                addresses", host))),
+        },
+        Err(e) => return Ok(PingResult::DNSIssue(format!("Unable to resolve hostname '{}' - \
+            error: {}", host, e))),
+    };
+
+    let (domain, protocol, echo_request_type, echo_reply_type) = match ip {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4, ICMP_ECHO_REQUEST_V4, ICMP_ECHO_REPLY_V4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6, ICMP_ECHO_REQUEST_V6, ICMP_ECHO_REPLY_V6),
+    };
+
+    let socket = Socket::new(domain, Type::DGRAM, Some(protocol))?;
+    let timeout = Duration::from_secs_f64(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+    if let Err(e) = socket.set_read_timeout(Some(timeout)) {
+        return Ok(PingResult::OSCmndIssue(format!("Unable to configure the native ICMP socket - \
+            error: {}", e)));
+    }
+
+    if config.continuous {
+        signal::install_interrupt_handler();
+    }
+
+    let identifier = std::process::id() as u16;
+    let count = if config.continuous { u32::MAX } else { config.count };
+    let dest = SockAddr::from(SocketAddr::new(ip, 0));
+    let mut rtts = Vec::new();
+    let mut timeouts = 0u32;
+
+    for sequence in 0..count {
+        if config.continuous && signal::interrupted() {
+            break;
+        }
+
+        let packet = build_echo_request(echo_request_type, identifier, sequence as u16);
+        let sent_at = Instant::now();
+
+        if let Err(e) = socket.send_to(&packet, &dest) {
+            return Ok(PingResult::OSCmndIssue(format!("Unable to send a native ICMP echo \
+                request - error: {}", e)));
+        }
+
+        let mut buf = [MaybeUninit::<u8>::uninit(); 512];
+
+        match socket.recv(&mut buf) {
+            Ok(received) => {
+                // Safety: `recv` guarantees the first `received` bytes of `buf` were
+                // initialized by the kernel.
+                let reply = unsafe {
+                    std::slice::from_raw_parts(buf.as_ptr() as *const u8, received)
+                };
+
+                if is_matching_echo_reply(reply, echo_reply_type, identifier, sequence as u16) {
+                    rtts.push(sent_at.elapsed());
+                } else {
+                    // A datagram arrived but it isn't the echo reply we asked for (e.g. a late
+                    // reply from an earlier sequence, or an ICMP error) - don't count it as a
+                    // successful pong.
+                    timeouts += 1;
+                }
+            }
+            Err(_) => {
+                // Includes WouldBlock/TimedOut (no reply arrived in time) as well as any other
+                // transient per-packet recv error - either way this sequence is lost, not a
+                // reason to abort a multi-packet run. Socket setup problems (e.g. permission
+                // errors creating the socket) are already handled before the loop starts.
+                timeouts += 1;
+            }
+        }
+
+        if sequence + 1 < count {
+            thread::sleep(Duration::from_secs_f64(config.interval_secs));
+        }
+    }
+
+    if rtts.is_empty() {
+        // Covers both "every packet timed out" and "no packet was ever sent" (--count 0, or an
+        // interrupt before the first recv completed) - neither is a reachable host, so don't
+        // fall through to ConnectionSuccess with 0 packets transmitted/received.
+        Ok(PingResult::ConnectionFailure(format!("Host '{}' did not respond to any native ICMP \
+            echo requests", host)))
+    } else {
+        Ok(PingResult::ConnectionSuccess(stats_from_replies(&rtts, timeouts)))
+    }
+}
+
+
+// Checks whether a received ICMP datagram is actually the echo reply we're waiting for - the
+// right type (0 for IPv4, 129 for IPv6) and the identifier/sequence number we sent - rather than
+// some other datagram delivered to the socket, such as a late reply for an earlier sequence or
+// an ICMP error.
+//
+fn is_matching_echo_reply(reply: &[u8], expected_type: u8, identifier: u16, sequence: u16) -> bool {
+    if reply.len() < 8 {
+        return false;
+    }
+
+    let reply_type = reply[0];
+    let reply_identifier = u16::from_be_bytes([reply[4], reply[5]]);
+    let reply_sequence = u16::from_be_bytes([reply[6], reply[7]]);
+    reply_type == expected_type && reply_identifier == identifier && reply_sequence == sequence
+}
+
+
+// Builds an ICMP echo-request packet (type 8 for IPv4, type 128 for IPv6; code 0) with the given
+// identifier and sequence number, and a correctly computed internet checksum.
+//
+fn build_echo_request(echo_request_type: u8, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![0u8; 8];
+    packet[0] = echo_request_type;
+    packet[1] = 0;
+    packet[4] = (identifier >> 8) as u8;
+    packet[5] = (identifier & 0xff) as u8;
+    packet[6] = (sequence >> 8) as u8;
+    packet[7] = (sequence & 0xff) as u8;
+
+    let checksum = internet_checksum(&packet);
+    packet[2] = (checksum >> 8) as u8;
+    packet[3] = (checksum & 0xff) as u8;
+    packet
+}
+
+
+// Computes the 16-bit one's-complement internet checksum (RFC 1071) over an ICMP header (with
+// its checksum field currently zeroed) and payload.
+//
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+    let mut chunks = data.chunks_exact(2);
+
+    for chunk in &mut chunks {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+
+    if let [last] = chunks.remainder() {
+        sum += (*last as u32) << 8;
+    }
+
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn internet_checksum_of_all_zero_header_is_all_ones() {
+        assert_eq!(internet_checksum(&[0u8; 8]), 0xffff);
+    }
+
+    #[test]
+    fn internet_checksum_handles_odd_length_input() {
+        // A trailing single byte is padded with a zero low byte, per RFC 1071.
+        assert_eq!(internet_checksum(&[0xff]), internet_checksum(&[0xff, 0x00]));
+    }
+
+    #[test]
+    fn build_echo_request_sets_type_identifier_and_sequence() {
+        let packet = build_echo_request(ICMP_ECHO_REQUEST_V4, 0x1234, 0x0001);
+        assert_eq!(packet[0], ICMP_ECHO_REQUEST_V4);
+        assert_eq!(packet[1], 0);
+        assert_eq!(u16::from_be_bytes([packet[4], packet[5]]), 0x1234);
+        assert_eq!(u16::from_be_bytes([packet[6], packet[7]]), 0x0001);
+    }
+
+    #[test]
+    fn build_echo_request_checksum_validates_as_zero_sum() {
+        let packet = build_echo_request(ICMP_ECHO_REQUEST_V4, 0xabcd, 7);
+        // Checksumming a correctly-checksummed header (checksum field included) always gives 0.
+        assert_eq!(internet_checksum(&packet), 0);
+    }
+
+    #[test]
+    fn matching_echo_reply_is_recognized() {
+        let reply = build_echo_request(ICMP_ECHO_REPLY_V4, 42, 3);
+        assert!(is_matching_echo_reply(&reply, ICMP_ECHO_REPLY_V4, 42, 3));
+    }
+
+    #[test]
+    fn echo_reply_with_wrong_identifier_is_rejected() {
+        let reply = build_echo_request(ICMP_ECHO_REPLY_V4, 42, 3);
+        assert!(!is_matching_echo_reply(&reply, ICMP_ECHO_REPLY_V4, 99, 3));
+    }
+
+    #[test]
+    fn echo_reply_with_wrong_sequence_is_rejected() {
+        let reply = build_echo_request(ICMP_ECHO_REPLY_V4, 42, 3);
+        assert!(!is_matching_echo_reply(&reply, ICMP_ECHO_REPLY_V4, 42, 4));
+    }
+
+    #[test]
+    fn echo_reply_with_wrong_type_is_rejected() {
+        let reply = build_echo_request(ICMP_ECHO_REQUEST_V4, 42, 3);
+        assert!(!is_matching_echo_reply(&reply, ICMP_ECHO_REPLY_V4, 42, 3));
+    }
+
+    #[test]
+    fn truncated_reply_is_rejected() {
+        assert!(!is_matching_echo_reply(&[0u8; 4], ICMP_ECHO_REPLY_V4, 42, 3));
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INSTALL_ONCE: Once = Once::new();
+
+
+// Whether a Ctrl-C / SIGINT has been observed since the handler was installed.
+//
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+
+// Installs a handler that flips an internal flag when the user sends Ctrl-C - SIGINT on Unix, a
+// CTRL_C/CTRL_BREAK console event on Windows - so a running continuous ping can stop cleanly and
+// still print its accumulated statistics instead of being killed outright. Safe to call more
+// than once; only the first call installs the handler.
+//
+pub fn install_interrupt_handler() {
+    INSTALL_ONCE.call_once(platform::install);
+}
+
+
+#[cfg(unix)]
+mod platform {
+    use super::{INTERRUPTED, Ordering};
+
+    extern "C" fn handle_sigint(_signum: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    pub fn install() {
+        unsafe {
+            libc::signal(libc::SIGINT, handle_sigint as *const () as libc::sighandler_t);
+        }
+    }
+}
+
+
+#[cfg(windows)]
+mod platform {
+    use super::{INTERRUPTED, Ordering};
+    use winapi::shared::minwindef::{BOOL, DWORD, TRUE};
+    use winapi::um::consoleapi::SetConsoleCtrlHandler;
+    use winapi::um::wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+
+    unsafe extern "system" fn handle_ctrl_event(ctrl_type: DWORD) -> BOOL {
+        if ctrl_type == CTRL_C_EVENT || ctrl_type == CTRL_BREAK_EVENT {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            TRUE
+        } else {
+            0
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(handle_ctrl_event), TRUE);
+        }
+    }
+}